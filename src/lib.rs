@@ -1,26 +1,281 @@
-use zed_extension_api as zed;
+use std::sync::Mutex;
 
-struct FileSystemOperationsExtension;
+use zed_extension_api::{self as zed, process, serde_json, settings::ContextServerSettings};
+
+mod provisioning;
+mod search_index;
+mod slash_commands;
+mod snapshots;
+mod worktree_util;
+
+struct FileSystemOperationsExtension {
+    /// `/search` needs settings resolved against a `Project`, which only
+    /// `context_server_command` receives; cached here for `run_slash_command`
+    /// (which only gets a `Worktree`) to reuse.
+    ///
+    /// `None` until `context_server_command` has fired at least once this
+    /// session (e.g. the "filesystem-mcp" context server has never been
+    /// started or is disabled). There is no `Worktree`-only path to read
+    /// `context_servers` settings, so `/search` falls back to defaults in
+    /// that case and says so in its own output rather than silently
+    /// ignoring the user's configured embedder.
+    search_settings: Mutex<Option<search_index::SemanticSearchSettings>>,
+}
+
+/// Extension settings nested under `context_servers.filesystem-mcp.settings`
+/// (separate from the `command` override, which uses Zed's own shape).
+#[derive(Debug, Default, serde::Deserialize)]
+struct FileSystemMcpSettings {
+    /// Pins the auto-provisioned release instead of tracking latest.
+    #[serde(default)]
+    version: Option<String>,
+    /// Force the auto-provisioned binary even when `uv` is on PATH.
+    /// Auto-provisioning also kicks in on its own whenever `uv` is absent,
+    /// so this is an override for the uncommon case, not the only trigger.
+    #[serde(default)]
+    managed: bool,
+    #[serde(default)]
+    search: search_index::SemanticSearchSettings,
+}
 
 impl zed::Extension for FileSystemOperationsExtension {
     fn new() -> Self {
-        Self
+        Self { search_settings: Mutex::new(None) }
     }
 
     fn context_server_command(
         &mut self,
         id: &zed::ContextServerId,
-        _project: &zed::Project,
+        project: &zed::Project,
     ) -> zed::Result<zed::Command> {
         match id.as_ref() {
-            "filesystem-mcp" => Ok(zed::Command {
-                command: "uv".to_string(),
-                args: vec!["run".to_string(), "filesystem-mcp".to_string()],
-                env: Default::default(),
-            }),
+            "filesystem-mcp" => {
+                let context_settings = ContextServerSettings::for_project(id.as_ref(), project)?;
+
+                let settings: FileSystemMcpSettings = context_settings
+                    .settings
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| format!("invalid settings for {}: {e}", id.as_ref()))?
+                    .unwrap_or_default();
+                *self.search_settings.lock().unwrap() = Some(settings.search);
+
+                if let Some(command) = context_settings.command {
+                    return Ok(resolve_command_override(command));
+                }
+
+                if should_auto_provision(settings.managed, uv_is_available()) {
+                    let binary_path = provisioning::provision(&provisioning::ProvisioningSettings {
+                        version: settings.version,
+                    })?;
+                    return Ok(zed::Command { command: binary_path, args: Vec::new(), env: Default::default() });
+                }
+
+                Ok(zed::Command {
+                    command: "uv".to_string(),
+                    args: vec!["run".to_string(), "filesystem-mcp".to_string()],
+                    env: Default::default(),
+                })
+            }
             _ => Err(format!("Unknown server: {}", id.as_ref())),
         }
     }
+
+    fn complete_slash_command_argument(
+        &self,
+        command: zed::SlashCommand,
+        args: Vec<String>,
+    ) -> zed::Result<Vec<zed::SlashCommandArgumentCompletion>> {
+        match command.name.as_str() {
+            // `complete_slash_command_argument` isn't given a `Worktree`, so
+            // path-based completion for read-file/list-dir/grep/search isn't
+            // possible here; checkpoint names, stored in the extension's own
+            // work directory, are the one case we can still complete.
+            "diff-snapshot" | "rollback" => {
+                let prefix = args.last().map(String::as_str).unwrap_or("");
+                Ok(snapshots::checkpoint_names()
+                    .into_iter()
+                    .filter(|name| name.starts_with(prefix))
+                    .map(|name| zed::SlashCommandArgumentCompletion {
+                        label: name.clone(),
+                        new_text: name,
+                        run_command: true,
+                    })
+                    .collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn run_slash_command(
+        &self,
+        command: zed::SlashCommand,
+        args: Vec<String>,
+        worktree: Option<&zed::Worktree>,
+    ) -> zed::Result<zed::SlashCommandOutput> {
+        let worktree =
+            worktree.ok_or_else(|| format!("/{} requires an open worktree", command.name))?;
+
+        match command.name.as_str() {
+            "read-file" | "list-dir" | "grep" => slash_commands::run(&command, &args, worktree),
+            "search" => {
+                let query = args.join(" ");
+                if query.is_empty() {
+                    return Err("/search requires a query".to_string());
+                }
+
+                let configured_settings = self.search_settings.lock().unwrap().clone();
+                let settings = configured_settings.clone().unwrap_or_default();
+                let results = search_index::search(worktree, &settings, &query, 8)?;
+                let output = search_index::to_output(results);
+
+                Ok(if configured_settings.is_some() {
+                    output
+                } else {
+                    with_notice(
+                        output,
+                        "Note: filesystem-mcp's context-server settings haven't loaded \
+                         this session yet (the \"filesystem-mcp\" context server hasn't \
+                         started), so /search is using the default local embedding \
+                         endpoint instead of your configured one.",
+                    )
+                })
+            }
+            "snapshot" => snapshots::snapshot(&command, &args, worktree),
+            "diff-snapshot" => snapshots::diff_snapshot(&args, worktree),
+            "rollback" => snapshots::rollback(&args, worktree),
+            _ => Err(format!("unknown slash command: \"{}\"", command.name)),
+        }
+    }
+}
+
+/// Whether `filesystem-mcp` should run from the auto-provisioned binary
+/// instead of `uv run filesystem-mcp`: either the user forced it via
+/// `managed`, or `uv` itself isn't usable, which is the case
+/// auto-provisioning exists to remove the hard Python-toolchain dependency
+/// for.
+fn should_auto_provision(managed: bool, uv_available: bool) -> bool {
+    managed || !uv_available
+}
+
+/// Checks whether `uv` is on PATH and actually runs, by asking the host to
+/// spawn it the same way `worktree_util`/`slash_commands` shell out to
+/// other platform tools.
+fn uv_is_available() -> bool {
+    process::Command::new("uv").arg("--version").output().is_ok_and(|output| output.status == Some(0))
+}
+
+/// Turns a user-provided `command` override into the `zed::Command` to launch.
+///
+/// The `uv run filesystem-mcp` default only makes sense as a unit: once the
+/// user points `path` at their own binary, defaulting `args` to `["run",
+/// "filesystem-mcp"]` would pass `uv`'s arguments to a binary that isn't
+/// `uv`. So the implicit args only apply when `path` itself is also unset.
+fn resolve_command_override(command: zed::settings::CommandSettings) -> zed::Command {
+    let has_custom_path = command.path.is_some();
+    zed::Command {
+        command: command.path.unwrap_or_else(|| "uv".to_string()),
+        args: command.arguments.unwrap_or_else(|| {
+            if has_custom_path {
+                Vec::new()
+            } else {
+                vec!["run".to_string(), "filesystem-mcp".to_string()]
+            }
+        }),
+        env: command.env.unwrap_or_default().into_iter().collect(),
+    }
+}
+
+/// Prepends `notice` as its own labeled section ahead of `output`'s
+/// existing sections, shifting their ranges to account for it.
+fn with_notice(output: zed::SlashCommandOutput, notice: &str) -> zed::SlashCommandOutput {
+    let mut text = format!("{notice}\n\n");
+    let offset = text.len() as u32;
+    text.push_str(&output.text);
+
+    let mut sections = vec![zed::SlashCommandOutputSection {
+        range: zed::Range { start: 0, end: offset },
+        label: "settings not yet loaded".to_string(),
+    }];
+    sections.extend(output.sections.into_iter().map(|mut section| {
+        section.range.start += offset;
+        section.range.end += offset;
+        section
+    }));
+
+    zed::SlashCommandOutput { text, sections }
 }
 
 zed::register_extension!(FileSystemOperationsExtension);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed::settings::CommandSettings;
+
+    #[test]
+    fn custom_path_without_arguments_does_not_inherit_uv_run() {
+        let command = resolve_command_override(CommandSettings {
+            path: Some("/usr/local/bin/filesystem-mcp".to_string()),
+            arguments: None,
+            env: None,
+        });
+
+        assert_eq!(command.command, "/usr/local/bin/filesystem-mcp");
+        assert!(command.args.is_empty());
+    }
+
+    #[test]
+    fn custom_path_with_explicit_arguments_keeps_them() {
+        let command = resolve_command_override(CommandSettings {
+            path: Some("pipx".to_string()),
+            arguments: Some(vec!["run".to_string(), "filesystem-mcp".to_string()]),
+            env: None,
+        });
+
+        assert_eq!(command.command, "pipx");
+        assert_eq!(command.args, vec!["run".to_string(), "filesystem-mcp".to_string()]);
+    }
+
+    #[test]
+    fn no_path_override_falls_back_to_uv_run() {
+        let command = resolve_command_override(CommandSettings { path: None, arguments: None, env: None });
+
+        assert_eq!(command.command, "uv");
+        assert_eq!(command.args, vec!["run".to_string(), "filesystem-mcp".to_string()]);
+    }
+
+    #[test]
+    fn auto_provisions_when_uv_is_absent() {
+        assert!(should_auto_provision(false, false));
+    }
+
+    #[test]
+    fn auto_provisions_when_managed_is_forced_even_with_uv_present() {
+        assert!(should_auto_provision(true, true));
+    }
+
+    #[test]
+    fn does_not_auto_provision_when_uv_present_and_not_forced() {
+        assert!(!should_auto_provision(false, true));
+    }
+
+    #[test]
+    fn with_notice_shifts_existing_section_ranges() {
+        let output = zed::SlashCommandOutput {
+            text: "result text".to_string(),
+            sections: vec![zed::SlashCommandOutputSection {
+                range: zed::Range { start: 0, end: 6 },
+                label: "result".to_string(),
+            }],
+        };
+
+        let annotated = with_notice(output, "heads up");
+
+        assert!(annotated.text.starts_with("heads up\n\n"));
+        assert!(annotated.text.ends_with("result text"));
+        assert_eq!(annotated.sections.len(), 2);
+        let shifted = &annotated.sections[1];
+        assert_eq!(&annotated.text[shifted.range.start as usize..shifted.range.end as usize], "result");
+    }
+}
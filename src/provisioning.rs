@@ -0,0 +1,111 @@
+use std::fs;
+
+use zed_extension_api as zed;
+
+const REPOSITORY: &str = "sandraschi/filesystem-mcp";
+const CACHED_VERSION_FILE: &str = "filesystem-mcp-version";
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct ProvisioningSettings {
+    /// Pin the downloaded release instead of tracking latest.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// Downloads and caches a pinned `filesystem-mcp` release binary, returning
+/// its path. Only called when the user hasn't configured a custom command
+/// and either `uv` isn't usable or the `managed` setting forces it, so this
+/// is the path that removes the hard dependency on the Python toolchain.
+pub(crate) fn provision(settings: &ProvisioningSettings) -> zed::Result<String> {
+    // A pinned version may not be the latest release, so its asset won't be
+    // in `latest_github_release`'s asset list; fetch that release directly
+    // by tag instead of searching the wrong one.
+    let release = match &settings.version {
+        Some(version) => zed::github_release_by_tag_name(REPOSITORY, version)?,
+        None => zed::latest_github_release(
+            REPOSITORY,
+            zed::GithubReleaseOptions { require_assets: true, pre_release: false },
+        )?,
+    };
+    let version = settings.version.clone().unwrap_or_else(|| release.version.clone());
+
+    let (platform, arch) = zed::current_platform();
+    let asset_name = asset_name(platform, arch, &version);
+    let install_dir = format!("filesystem-mcp-{version}");
+    let binary_path = format!("{install_dir}/{}", binary_name(platform));
+
+    if fs::metadata(&binary_path).is_ok() && cached_version() == Some(version.clone()) {
+        return Ok(binary_path);
+    }
+
+    let asset = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == asset_name)
+        .ok_or_else(|| format!("no release asset named {asset_name} for {version}"))?;
+
+    let file_type = if matches!(platform, zed::Os::Windows) {
+        zed::DownloadedFileType::Zip
+    } else {
+        zed::DownloadedFileType::GzipTar
+    };
+    zed::download_file(&asset.download_url, &install_dir, file_type)?;
+    zed::make_file_executable(&binary_path)?;
+
+    let _ = fs::write(CACHED_VERSION_FILE, &version);
+    Ok(binary_path)
+}
+
+fn cached_version() -> Option<String> {
+    fs::read_to_string(CACHED_VERSION_FILE).ok()
+}
+
+fn binary_name(platform: zed::Os) -> &'static str {
+    match platform {
+        zed::Os::Windows => "filesystem-mcp.exe",
+        zed::Os::Mac | zed::Os::Linux => "filesystem-mcp",
+    }
+}
+
+fn asset_name(platform: zed::Os, arch: zed::Architecture, version: &str) -> String {
+    let os = match platform {
+        zed::Os::Windows => "windows",
+        zed::Os::Mac => "macos",
+        zed::Os::Linux => "linux",
+    };
+    let arch = match arch {
+        zed::Architecture::Aarch64 => "aarch64",
+        zed::Architecture::X86 => "x86",
+        zed::Architecture::X8664 => "x86_64",
+    };
+    let ext = if matches!(platform, zed::Os::Windows) { "zip" } else { "tar.gz" };
+    format!("filesystem-mcp-{version}-{arch}-{os}.{ext}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_name_adds_exe_extension_on_windows() {
+        assert_eq!(binary_name(zed::Os::Windows), "filesystem-mcp.exe");
+        assert_eq!(binary_name(zed::Os::Mac), "filesystem-mcp");
+        assert_eq!(binary_name(zed::Os::Linux), "filesystem-mcp");
+    }
+
+    #[test]
+    fn asset_name_matches_release_naming_per_platform() {
+        assert_eq!(
+            asset_name(zed::Os::Linux, zed::Architecture::X8664, "v1.2.3"),
+            "filesystem-mcp-v1.2.3-x86_64-linux.tar.gz"
+        );
+        assert_eq!(
+            asset_name(zed::Os::Windows, zed::Architecture::Aarch64, "v1.2.3"),
+            "filesystem-mcp-v1.2.3-aarch64-windows.zip"
+        );
+        assert_eq!(
+            asset_name(zed::Os::Mac, zed::Architecture::X86, "v1.2.3"),
+            "filesystem-mcp-v1.2.3-x86-macos.tar.gz"
+        );
+    }
+}
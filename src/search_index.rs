@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use zed_extension_api::{self as zed, serde_json, SlashCommandOutput, SlashCommandOutputSection, Worktree};
+
+use crate::worktree_util;
+
+const INDEX_FILE: &str = "semantic-search-index.json";
+const CHUNK_TOKENS: usize = 512;
+const CHUNK_OVERLAP_TOKENS: usize = 64;
+/// Rough chars-per-token estimate; good enough for windowing, not for billing.
+const CHARS_PER_TOKEN: usize = 4;
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+pub(crate) struct SemanticSearchSettings {
+    #[serde(default)]
+    pub embedding_endpoint: Option<String>,
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct Chunk {
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct FileRecord {
+    content_hash: u64,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+struct Index {
+    files: HashMap<String, FileRecord>,
+}
+
+pub(crate) struct SearchResult {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub score: f32,
+    pub text: String,
+}
+
+/// Re-embeds any file whose content changed since the last run, prunes
+/// entries for files that no longer exist, then ranks all indexed chunks
+/// against `query` and returns the top `limit`.
+///
+/// The first call on a fresh worktree embeds every file and can take a
+/// while; no progress is reported while it runs, and the call returns only
+/// once the index is built and the query ranked. Once persisted, later calls
+/// only re-embed files whose content hash has changed, so edits that don't
+/// change a file's byte length (e.g. swapping two equal-length identifiers)
+/// are still caught. Deleted files are dropped from the index rather than
+/// left to grow it forever and surface stale, unreadable results.
+pub(crate) fn search(
+    worktree: &Worktree,
+    settings: &SemanticSearchSettings,
+    query: &str,
+    limit: usize,
+) -> zed::Result<Vec<SearchResult>> {
+    let index_path = Path::new(INDEX_FILE);
+    let mut index = load_index(index_path);
+
+    let paths = worktree_util::list_files(worktree)?;
+    let mut changed = false;
+    for path in &paths {
+        let Ok(text) = worktree.read_text_file(path) else {
+            continue;
+        };
+        let content_hash = hash_content(&text);
+
+        let needs_reembed =
+            index.files.get(path).map(|record| record.content_hash != content_hash).unwrap_or(true);
+        if !needs_reembed {
+            continue;
+        }
+
+        changed = true;
+        let chunks = chunk_file(&text)
+            .into_iter()
+            .map(|(start_line, end_line, text)| {
+                let embedding = embed(settings, &text)?;
+                Ok(Chunk { path: path.clone(), start_line, end_line, embedding })
+            })
+            .collect::<zed::Result<Vec<_>>>()?;
+
+        index.files.insert(path.clone(), FileRecord { content_hash, chunks });
+    }
+
+    if prune_deleted(&mut index, &paths) {
+        changed = true;
+    }
+
+    if changed {
+        save_index(index_path, &index);
+    }
+
+    let query_embedding = embed(settings, query)?;
+    let mut scored: Vec<SearchResult> = index
+        .files
+        .values()
+        .flat_map(|record| &record.chunks)
+        .map(|chunk| SearchResult {
+            path: chunk.path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            score: cosine_similarity(&query_embedding, &chunk.embedding),
+            text: String::new(),
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(limit);
+
+    for result in &mut scored {
+        if let Ok(text) = worktree.read_text_file(&result.path) {
+            result.text = text
+                .lines()
+                .skip(result.start_line)
+                .take(result.end_line - result.start_line)
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+    }
+
+    Ok(scored)
+}
+
+/// Renders ranked results as one labeled section per chunk, matching how
+/// the other filesystem slash commands report their output.
+pub(crate) fn to_output(results: Vec<SearchResult>) -> SlashCommandOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    for result in results {
+        let label = format!(
+            "{} ({}-{}, score {:.2})",
+            result.path, result.start_line + 1, result.end_line, result.score
+        );
+        let start = text.len();
+        text.push_str(&result.text);
+        text.push('\n');
+        let range = zed::Range { start: start as u32, end: text.len() as u32 };
+        sections.push(SlashCommandOutputSection { range, label });
+    }
+
+    SlashCommandOutput { text, sections }
+}
+
+fn chunk_file(text: &str) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let window_chars = CHUNK_TOKENS * CHARS_PER_TOKEN;
+    let overlap_chars = CHUNK_OVERLAP_TOKENS * CHARS_PER_TOKEN;
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start;
+        let mut len = 0;
+        while end < lines.len() && len < window_chars {
+            len += lines[end].len() + 1;
+            end += 1;
+        }
+        chunks.push((start, end, lines[start..end].join("\n")));
+        if end == lines.len() {
+            break;
+        }
+        let mut back = 0;
+        let mut new_start = end;
+        while new_start > start && back < overlap_chars {
+            new_start -= 1;
+            back += lines[new_start].len() + 1;
+        }
+        start = new_start.max(start + 1);
+    }
+    chunks
+}
+
+fn embed(settings: &SemanticSearchSettings, text: &str) -> zed::Result<Vec<f32>> {
+    let endpoint = settings
+        .embedding_endpoint
+        .clone()
+        .unwrap_or_else(|| "http://localhost:11434/api/embeddings".to_string());
+    let model = settings
+        .embedding_model
+        .clone()
+        .unwrap_or_else(|| "nomic-embed-text".to_string());
+
+    let request = zed::http_client::HttpRequest::builder()
+        .method(zed::http_client::HttpMethod::Post)
+        .url(endpoint)
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "model": model, "prompt": text }).to_string().into_bytes())
+        .build()?;
+
+    let response = zed::http_client::fetch(&request)
+        .map_err(|e| format!("embedding request failed: {e}"))?;
+    let body: serde_json::Value = serde_json::from_slice(&response.body)
+        .map_err(|e| format!("embedding response was not valid JSON: {e}"))?;
+
+    body["embedding"]
+        .as_array()
+        .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+        .ok_or_else(|| "embedding response missing \"embedding\" array".to_string())
+}
+
+/// Drops index entries for files no longer present in `current_paths`, so
+/// deleted files' chunks don't keep scoring in search results (with stale
+/// or empty text once they fail to read) and the index doesn't grow
+/// unboundedly. Returns whether anything was removed.
+fn prune_deleted(index: &mut Index, current_paths: &[String]) -> bool {
+    let current: std::collections::HashSet<&String> = current_paths.iter().collect();
+    let before = index.files.len();
+    index.files.retain(|path, _| current.contains(path));
+    index.files.len() != before
+}
+
+/// Hashes file content to detect changes that a size comparison would miss
+/// (e.g. swapping two equal-length identifiers).
+fn hash_content(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn load_index(path: &Path) -> Index {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(path: &Path, index: &Index) {
+    if let Ok(content) = serde_json::to_string(index) {
+        let _ = fs::write(path, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_content_differs_for_equal_length_edits() {
+        let original = hash_content("let ab = 1;\nlet cd = 2;");
+        let swapped = hash_content("let cd = 1;\nlet ab = 2;");
+        assert_ne!(original, swapped);
+    }
+
+    #[test]
+    fn hash_content_is_stable_for_unchanged_text() {
+        let text = "unchanged file contents";
+        assert_eq!(hash_content(text), hash_content(text));
+    }
+
+    #[test]
+    fn cosine_similarity_of_identical_vectors_is_one() {
+        let vector = vec![1.0, 2.0, 3.0];
+        assert!((cosine_similarity(&vector, &vector) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn cosine_similarity_with_zero_vector_is_zero() {
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn prune_deleted_removes_entries_not_in_current_paths() {
+        let mut index = Index::default();
+        index.files.insert("kept.rs".to_string(), FileRecord { content_hash: 1, chunks: Vec::new() });
+        index.files.insert("deleted.rs".to_string(), FileRecord { content_hash: 2, chunks: Vec::new() });
+
+        let changed = prune_deleted(&mut index, &["kept.rs".to_string()]);
+
+        assert!(changed);
+        assert_eq!(index.files.len(), 1);
+        assert!(index.files.contains_key("kept.rs"));
+    }
+
+    #[test]
+    fn prune_deleted_reports_no_change_when_nothing_removed() {
+        let mut index = Index::default();
+        index.files.insert("kept.rs".to_string(), FileRecord { content_hash: 1, chunks: Vec::new() });
+
+        let changed = prune_deleted(&mut index, &["kept.rs".to_string()]);
+
+        assert!(!changed);
+        assert_eq!(index.files.len(), 1);
+    }
+
+    #[test]
+    fn chunk_file_covers_every_line_with_overlap() {
+        let text = (0..2000).map(|n| format!("line {n}")).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_file(&text);
+
+        assert!(!chunks.is_empty());
+        assert_eq!(chunks.first().unwrap().0, 0);
+        assert_eq!(chunks.last().unwrap().1, 2000);
+        for window in chunks.windows(2) {
+            assert!(window[1].0 < window[0].1, "consecutive chunks should overlap");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use zed_extension_api::{self as zed, process, SlashCommand, SlashCommandOutput, SlashCommandOutputSection, Worktree};
+
+use crate::worktree_util::EXCLUDED_DIRS;
+
+/// Handles the `/read-file`, `/list-dir`, and `/grep` slash commands, each of
+/// which operates on the current worktree and returns its result as a single
+/// labeled section.
+///
+/// `list-dir` and `grep` shell out to the platform's own directory listing
+/// and text search (`ls`/`grep` on Unix, `dir`/`findstr` on Windows) rather
+/// than re-implementing directory traversal, since `Worktree` only exposes
+/// `read_text_file` for a single known path.
+pub(crate) fn run(
+    command: &SlashCommand,
+    args: &[String],
+    worktree: &Worktree,
+) -> Result<SlashCommandOutput, String> {
+    match command.name.as_str() {
+        "read-file" => {
+            let path = first_arg(args, "read-file")?;
+            let text = worktree
+                .read_text_file(path)
+                .map_err(|e| format!("failed to read {path}: {e}"))?;
+
+            Ok(labeled_output(path.to_string(), text))
+        }
+        "list-dir" => {
+            let path = first_arg(args, "list-dir")?;
+            let target = format!("{}/{path}", worktree.root_path());
+            let (platform, _) = zed::current_platform();
+            let output = if matches!(platform, zed::Os::Windows) {
+                // `dir` has no standalone executable, so this still has to
+                // go through `cmd /C`, which re-joins and re-parses its
+                // whole argument list with its own shell grammar. Quote the
+                // user-controlled path so `&|<>^` inside it can't be read
+                // as a second command.
+                process::Command::new("cmd").args(["/C", "dir", "/b", &cmd_escape(&target)]).output()
+            } else {
+                process::Command::new("ls").args(["-1", &target]).output()
+            }
+            .map_err(|e| format!("failed to list {path}: {e}"))?;
+
+            Ok(labeled_output(path.to_string(), String::from_utf8_lossy(&output.stdout).into_owned()))
+        }
+        "grep" => {
+            let pattern = args.join(" ");
+            if pattern.is_empty() {
+                return Err("/grep requires a pattern".to_string());
+            }
+            let root = worktree.root_path();
+            let (platform, _) = zed::current_platform();
+            let output = if matches!(platform, zed::Os::Windows) {
+                // `findstr.exe` is a real standalone executable, so invoke
+                // it directly instead of through `cmd /C`: there's no shell
+                // re-parsing a pattern containing `&|<>^` to escape around.
+                process::Command::new("findstr")
+                    .args(["/S", "/N", &format!("/C:{pattern}"), &format!("{root}\\*")])
+                    .output()
+            } else {
+                let mut grep = process::Command::new("grep").arg("-rn");
+                for dir in EXCLUDED_DIRS {
+                    grep = grep.args(["--exclude-dir", dir]);
+                }
+                // `--` stops option parsing so a pattern starting with `-`
+                // (e.g. `/grep -rf /`) is treated as the search text, not a flag.
+                grep.args(["--", &pattern, &root]).output()
+            }
+            .map_err(|e| format!("grep failed: {e}"))?;
+
+            Ok(labeled_output(
+                format!("grep \"{pattern}\""),
+                String::from_utf8_lossy(&output.stdout).into_owned(),
+            ))
+        }
+        _ => Err(format!("unknown slash command: \"{}\"", command.name)),
+    }
+}
+
+fn first_arg<'a>(args: &'a [String], command: &str) -> Result<&'a String, String> {
+    args.first()
+        .ok_or_else(|| format!("/{command} requires an argument"))
+}
+
+/// Quotes `arg` for safe embedding in a `cmd.exe /C` command line.
+///
+/// `cmd /C` re-joins and re-parses its entire argument list with its own
+/// shell grammar, so `&|<>^` in an unquoted argument can break out into a
+/// second command. Wrapping the argument in double quotes keeps it in a
+/// single token cmd treats as literal text; doubling any embedded `"` keeps
+/// it from ending that quoted region early.
+fn cmd_escape(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('"', "\"\""))
+}
+
+fn labeled_output(label: String, text: String) -> SlashCommandOutput {
+    let range = zed_extension_api::Range { start: 0, end: text.len() as u32 };
+    SlashCommandOutput {
+        sections: vec![SlashCommandOutputSection { range, label }],
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cmd_escape_wraps_in_quotes() {
+        assert_eq!(cmd_escape("C:\\Users\\me"), "\"C:\\Users\\me\"");
+    }
+
+    #[test]
+    fn cmd_escape_neutralizes_shell_metacharacters() {
+        let escaped = cmd_escape("foo & calc.exe");
+        assert_eq!(escaped, "\"foo & calc.exe\"");
+        assert!(escaped.starts_with('"') && escaped.ends_with('"'));
+    }
+
+    #[test]
+    fn cmd_escape_doubles_embedded_quotes() {
+        assert_eq!(cmd_escape("a\"b"), "\"a\"\"b\"");
+    }
+}
@@ -0,0 +1,352 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use zed_extension_api::{
+    serde_json, Range, SlashCommand, SlashCommandOutput, SlashCommandOutputSection, Worktree,
+};
+
+use crate::worktree_util;
+
+const MANIFEST_FILE: &str = "snapshots-manifest.json";
+const STORE_DIR: &str = "snapshots";
+/// Caps how much restored file content `/rollback` dumps into the
+/// assistant's context in one go; a checkpoint over many/large files would
+/// otherwise produce an unbounded amount of output.
+const MAX_ROLLBACK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+struct Manifest {
+    /// Monotonic revision counter used in place of wall-clock timestamps,
+    /// which the extension sandbox has no access to.
+    next_revision: u64,
+    checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct Checkpoint {
+    name: String,
+    revision: u64,
+    files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct SnapshotFile {
+    path: String,
+    blob: String,
+}
+
+/// Captures the current contents of every worktree file outside
+/// `worktree_util::EXCLUDED_DIRS` into a content-addressed blob store keyed
+/// by a hash of each file's bytes, and records the set as a named checkpoint
+/// in the manifest. Files that can't be read as text are not captured.
+///
+/// Because blobs are keyed by content rather than by `path`/revision,
+/// snapshotting the same unchanged file across many checkpoints (the
+/// expected pattern for "undo boundary around a whole agent turn") writes
+/// it to disk exactly once — `write_blob` skips the write when a blob with
+/// that hash already exists.
+pub(crate) fn snapshot(
+    command: &SlashCommand,
+    args: &[String],
+    worktree: &Worktree,
+) -> Result<SlashCommandOutput, String> {
+    let name = first_arg(args, &command.name)?;
+    let mut manifest = load_manifest();
+    let revision = manifest.next_revision;
+    manifest.next_revision += 1;
+
+    let mut files = Vec::new();
+    for path in worktree_util::list_files(worktree)? {
+        let Ok(content) = worktree.read_text_file(&path) else {
+            continue;
+        };
+        let blob = blob_name(&content);
+        write_blob(&blob, &content)?;
+        files.push(SnapshotFile { path, blob });
+    }
+
+    let file_count = files.len();
+    manifest.checkpoints.push(Checkpoint { name: name.clone(), revision, files });
+    save_manifest(&manifest)?;
+
+    Ok(simple_output(format!(
+        "Snapshot \"{name}\" captured {file_count} file(s) as checkpoint revision {revision}."
+    )))
+}
+
+/// Emits a unified diff between the checkpoint's captured contents and the
+/// worktree's current contents, one section per changed file.
+pub(crate) fn diff_snapshot(
+    args: &[String],
+    worktree: &Worktree,
+) -> Result<SlashCommandOutput, String> {
+    let name = first_arg(args, "diff-snapshot")?;
+    let checkpoint = find_checkpoint(name)?;
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    for file in &checkpoint.files {
+        let before = read_blob(&file.blob).unwrap_or_default();
+        let after = worktree.read_text_file(&file.path).unwrap_or_default();
+        if before == after {
+            continue;
+        }
+
+        let start = text.len();
+        text.push_str(&unified_diff(&file.path, &before, &after));
+        sections.push(SlashCommandOutputSection { range: byte_range(start, text.len()), label: file.path.clone() });
+    }
+
+    Ok(SlashCommandOutput { text, sections })
+}
+
+/// `Worktree` has no write capability, so `/rollback` cannot restore a
+/// checkpoint atomically itself. Instead it reports the exact contents each
+/// changed file needs to be written back to, for the assistant to apply
+/// with its own file-editing tool — it is a restore *plan*, not a restore.
+/// Output is capped at `MAX_ROLLBACK_BYTES`; a checkpoint with more changed
+/// content than that is reported as partial, with the remaining files named
+/// but not dumped, so a large checkpoint can't blow up the assistant's
+/// context in one call.
+pub(crate) fn rollback(args: &[String], worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+    let name = first_arg(args, "rollback")?;
+    let checkpoint = find_checkpoint(name)?;
+
+    let mut text = format!(
+        "Checkpoint \"{name}\" (revision {}) was NOT restored automatically — this \
+         extension can only read project files. Apply the following file contents \
+         yourself to complete the restore:\n\n",
+        checkpoint.revision
+    );
+    let mut sections = Vec::new();
+    let mut omitted = Vec::new();
+    for file in &checkpoint.files {
+        let restored = read_blob(&file.blob)?;
+        if worktree.read_text_file(&file.path).ok().as_deref() == Some(restored.as_str()) {
+            continue;
+        }
+
+        if text.len() + restored.len() > MAX_ROLLBACK_BYTES {
+            omitted.push(file.path.clone());
+            continue;
+        }
+
+        let start = text.len();
+        text.push_str(&format!("--- {} ---\n{restored}\n\n", file.path));
+        sections.push(SlashCommandOutputSection { range: byte_range(start, text.len()), label: file.path.clone() });
+    }
+
+    if !omitted.is_empty() {
+        let start = text.len();
+        text.push_str(&format!(
+            "{} more file(s) omitted because this restore plan exceeded {MAX_ROLLBACK_BYTES} \
+             bytes: {}. Re-run /diff-snapshot \"{name}\" to review them individually.\n",
+            omitted.len(),
+            omitted.join(", ")
+        ));
+        sections.push(SlashCommandOutputSection {
+            range: byte_range(start, text.len()),
+            label: "omitted (too large)".to_string(),
+        });
+    }
+
+    Ok(SlashCommandOutput { text, sections })
+}
+
+/// Checkpoint names for argument completion on `/diff-snapshot` and `/rollback`.
+pub(crate) fn checkpoint_names() -> Vec<String> {
+    load_manifest().checkpoints.into_iter().map(|checkpoint| checkpoint.name).collect()
+}
+
+fn find_checkpoint(name: &str) -> Result<Checkpoint, String> {
+    load_manifest()
+        .checkpoints
+        .into_iter()
+        .rev()
+        .find(|checkpoint| checkpoint.name == name)
+        .ok_or_else(|| format!("no snapshot named \"{name}\""))
+}
+
+fn first_arg<'a>(args: &'a [String], command: &str) -> Result<&'a String, String> {
+    args.first()
+        .ok_or_else(|| format!("/{command} requires a checkpoint name"))
+}
+
+fn byte_range(start: usize, end: usize) -> Range {
+    Range { start: start as u32, end: end as u32 }
+}
+
+fn simple_output(text: String) -> SlashCommandOutput {
+    let range = byte_range(0, text.len());
+    SlashCommandOutput {
+        sections: vec![SlashCommandOutputSection { range, label: "snapshot".to_string() }],
+        text,
+    }
+}
+
+/// Hashes file content into the blob store's key, so identical content
+/// (whether from the same file across snapshots or different files
+/// entirely) is stored exactly once.
+fn blob_name(content: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn blob_path(blob: &str) -> PathBuf {
+    Path::new(STORE_DIR).join(blob)
+}
+
+fn write_blob(blob: &str, content: &str) -> Result<(), String> {
+    fs::create_dir_all(STORE_DIR).map_err(|e| format!("failed to create snapshot store: {e}"))?;
+    let path = blob_path(blob);
+    if fs::metadata(&path).is_ok() {
+        return Ok(());
+    }
+    fs::write(path, content).map_err(|e| format!("failed to write snapshot blob: {e}"))
+}
+
+fn read_blob(blob: &str) -> Result<String, String> {
+    fs::read_to_string(blob_path(blob)).map_err(|e| format!("failed to read snapshot blob: {e}"))
+}
+
+fn load_manifest() -> Manifest {
+    fs::read_to_string(MANIFEST_FILE)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(manifest: &Manifest) -> Result<(), String> {
+    let content = serde_json::to_string(manifest).map_err(|e| e.to_string())?;
+    fs::write(MANIFEST_FILE, content).map_err(|e| format!("failed to save snapshot manifest: {e}"))
+}
+
+/// Minimal line-based unified diff; good enough for reviewing checkpoint
+/// drift without pulling in a diff crate.
+fn unified_diff(path: &str, before: &str, after: &str) -> String {
+    let before_lines: Vec<&str> = before.lines().collect();
+    let after_lines: Vec<&str> = after.lines().collect();
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    if before_lines.len().saturating_mul(after_lines.len()) > diff::MAX_TABLE_CELLS {
+        out.push_str(
+            "(file too large for a line-aligned diff; showing a coarse whole-file diff instead)\n",
+        );
+    }
+    for line in diff::lines(&before_lines, &after_lines) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+mod diff {
+    /// Above this many (line_a × line_b) table cells, the LCS table below
+    /// would allocate tens of megabytes for a single changed file (a
+    /// lockfile, a generated bundle); fall back to a coarse whole-file diff
+    /// instead of risking that allocation in the WASM sandbox.
+    pub(super) const MAX_TABLE_CELLS: usize = 4_000_000;
+
+    /// Longest-common-subsequence based line diff, emitting `-`/`+`/` `
+    /// prefixed lines like a unified diff body (without hunk headers).
+    pub(super) fn lines(a: &[&str], b: &[&str]) -> Vec<String> {
+        if a.len().saturating_mul(b.len()) > MAX_TABLE_CELLS {
+            return coarse_lines(a, b);
+        }
+
+        let lcs = longest_common_subsequence(a, b);
+        let mut out = Vec::new();
+        let (mut i, mut j, mut k) = (0, 0, 0);
+        while i < a.len() || j < b.len() {
+            if k < lcs.len() && i < a.len() && j < b.len() && a[i] == lcs[k] && b[j] == lcs[k] {
+                out.push(format!(" {}", a[i]));
+                i += 1;
+                j += 1;
+                k += 1;
+            } else if i < a.len() && (k >= lcs.len() || a[i] != lcs[k]) {
+                out.push(format!("-{}", a[i]));
+                i += 1;
+            } else {
+                out.push(format!("+{}", b[j]));
+                j += 1;
+            }
+        }
+        out
+    }
+
+    /// Whole-file fallback used when the LCS table would be too large to
+    /// allocate safely: every old line removed, every new line added, with
+    /// no attempt at line alignment.
+    fn coarse_lines(a: &[&str], b: &[&str]) -> Vec<String> {
+        let mut out = Vec::with_capacity(a.len() + b.len());
+        out.extend(a.iter().map(|line| format!("-{line}")));
+        out.extend(b.iter().map(|line| format!("+{line}")));
+        out
+    }
+
+    fn longest_common_subsequence<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<&'a str> {
+        let (n, m) = (a.len(), b.len());
+        let mut table = vec![vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                table[i][j] = if a[i] == b[j] {
+                    table[i + 1][j + 1] + 1
+                } else {
+                    table[i + 1][j].max(table[i][j + 1])
+                };
+            }
+        }
+
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if a[i] == b[j] {
+                result.push(a[i]);
+                i += 1;
+                j += 1;
+            } else if table[i + 1][j] >= table[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_name_is_stable_for_identical_content() {
+        assert_eq!(blob_name("same content"), blob_name("same content"));
+    }
+
+    #[test]
+    fn blob_name_differs_for_different_content() {
+        assert_ne!(blob_name("content a"), blob_name("content b"));
+    }
+
+    #[test]
+    fn unified_diff_marks_added_and_removed_lines() {
+        let diff = unified_diff("file.txt", "one\ntwo\nthree", "one\nthree\nfour");
+        assert!(diff.contains("-two"));
+        assert!(diff.contains("+four"));
+        assert!(diff.contains(" one"));
+        assert!(diff.contains(" three"));
+    }
+
+    #[test]
+    fn unified_diff_falls_back_for_oversized_inputs() {
+        let before: Vec<String> = (0..3000).map(|n| format!("line {n}")).collect();
+        let after: Vec<String> = (0..3000).map(|n| format!("line {n} edited")).collect();
+        let diff = unified_diff("big.txt", &before.join("\n"), &after.join("\n"));
+
+        assert!(diff.contains("coarse whole-file diff"));
+        assert!(diff.contains("-line 0"));
+        assert!(diff.contains("+line 0 edited"));
+    }
+}
@@ -0,0 +1,33 @@
+use zed_extension_api::{self as zed, process, Worktree};
+
+/// Directories that are never useful to index, search, or snapshot: VCS
+/// metadata, dependency trees, and build output. Walking these blows up
+/// indexing time and snapshot disk usage without adding anything a user
+/// would search for.
+pub(crate) const EXCLUDED_DIRS: &[&str] =
+    &[".git", "node_modules", "target", ".venv", "venv", "dist", "build", "__pycache__"];
+
+/// Lists every regular file under the worktree root, skipping `EXCLUDED_DIRS`.
+///
+/// The extension API has no directory-listing call of its own (`Worktree`
+/// only exposes `read_text_file` for a known path), so this shells out to
+/// the platform's own recursive directory listing the same way a
+/// language-server extension shells out to its toolchain.
+pub(crate) fn list_files(worktree: &Worktree) -> Result<Vec<String>, String> {
+    let root = worktree.root_path();
+    let (platform, _) = zed::current_platform();
+    let output = if matches!(platform, zed::Os::Windows) {
+        process::Command::new("cmd").args(["/C", "dir", root.as_str(), "/s", "/b", "/a:-d"]).output()
+    } else {
+        process::Command::new("find").args([root.as_str(), "-type", "f"]).output()
+    }
+    .map_err(|e| format!("failed to list files under {root}: {e}"))?;
+
+    let prefix = format!("{}/", root.replace('\\', "/"));
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.replace('\\', "/"))
+        .filter_map(|path| path.strip_prefix(prefix.as_str()).map(str::to_string))
+        .filter(|relative| !relative.split('/').any(|part| EXCLUDED_DIRS.contains(&part)))
+        .collect())
+}